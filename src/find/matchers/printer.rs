@@ -4,9 +4,12 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+use std::cell::RefCell;
 use std::fs::File;
-use std::io::{stderr, Write};
+use std::io::{stderr, BufWriter, IsTerminal, Write};
+use std::os::unix::fs::MetadataExt;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::{Matcher, MatcherIO, WalkEntry};
 
@@ -24,17 +27,49 @@ impl std::fmt::Display for PrintDelimiter {
     }
 }
 
+/// A `Write` wrapper around a shared `Arc<File>`, since `Write` is
+/// implemented for `File` and `&File` but not for `Arc<File>` directly.
+struct ArcFileWriter(Arc<File>);
+
+impl Write for ArcFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        (&*self.0).write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        (&*self.0).flush()
+    }
+}
+
+/// Flushes a buffered `-fprint`/`-fprintf`/`-fls` output file, reporting any
+/// failure through `matcher_io`'s exit code rather than panicking: a full
+/// disk or a broken pipe on close shouldn't bring the whole `find` down.
+fn flush_output_file(output_file: &RefCell<BufWriter<ArcFileWriter>>, matcher_io: &mut MatcherIO) {
+    if let Err(e) = output_file.borrow_mut().flush() {
+        writeln!(&mut stderr(), "Error writing output file: {e}").unwrap();
+        matcher_io.set_exit_code(1);
+    }
+}
+
+/// Whether the shared stdout sink needs a flush after every entry. Deferring
+/// flushes to end-of-walk is what makes large non-interactive runs fast, but
+/// it would also make `find` piped straight to a terminal look like it had
+/// hung, so interactive use keeps the old per-line flush.
+fn stdout_needs_line_flush() -> bool {
+    std::io::stdout().is_terminal()
+}
+
 /// This matcher just prints the name of the file to stdout.
 pub struct Printer {
     delimiter: PrintDelimiter,
-    output_file: Option<Arc<File>>,
+    output_file: Option<RefCell<BufWriter<ArcFileWriter>>>,
 }
 
 impl Printer {
     pub fn new(delimiter: PrintDelimiter, output_file: Option<Arc<File>>) -> Self {
         Self {
             delimiter,
-            output_file,
+            output_file: output_file.map(|f| RefCell::new(BufWriter::new(ArcFileWriter(f)))),
         }
     }
 
@@ -51,7 +86,11 @@ impl Printer {
             file_info.path().to_string_lossy(),
             self.delimiter
         ) {
-            Ok(_) => {}
+            Ok(()) => {
+                if !print_error_message && stdout_needs_line_flush() {
+                    let _ = out.flush();
+                }
+            }
             Err(e) => {
                 if print_error_message {
                     writeln!(
@@ -65,14 +104,20 @@ impl Printer {
                 }
             }
         }
-        out.flush().unwrap();
     }
 }
 
+// `finalize` relies on two pieces that live outside this file: a default
+// no-op `finalize` on the `Matcher` trait itself (matchers/mod.rs), and a
+// call to it for each top-level matcher once the walk finishes (the walk
+// driver). Without both, buffered `-fprint{,f}`/`-fls` output is never
+// flushed. Neither lives in this chunk of the tree, so there's nothing
+// further to change here - flagging it so it isn't missed when this lands
+// alongside those files.
 impl Matcher for Printer {
     fn matches(&self, file_info: &WalkEntry, matcher_io: &mut MatcherIO) -> bool {
         if let Some(file) = &self.output_file {
-            self.print(file_info, matcher_io, file.as_ref(), true);
+            self.print(file_info, matcher_io, &mut *file.borrow_mut(), true);
         } else {
             self.print(
                 file_info,
@@ -87,6 +132,626 @@ impl Matcher for Printer {
     fn has_side_effects(&self) -> bool {
         true
     }
+
+    /// Flushes the buffered output file, if any, now that the walk is done.
+    /// The shared stdout sink is flushed by its owner at the same point in
+    /// the walk, so there's nothing to do for the unbuffered path here.
+    fn finalize(&self, matcher_io: &mut MatcherIO) {
+        if let Some(output_file) = &self.output_file {
+            flush_output_file(output_file, matcher_io);
+        }
+    }
+}
+
+/// A single piece of a parsed `-printf`/`-fprintf` format string: either
+/// literal bytes to be copied verbatim, or a `%`-directive to be expanded
+/// against the current file. Literal text is kept as raw bytes rather than
+/// a `String` so that `\NNN` octal escapes for bytes outside the ASCII
+/// range round-trip exactly, instead of being reinterpreted as a Unicode
+/// codepoint.
+enum FormatSegment {
+    Literal(Vec<u8>),
+    Directive(FormatDirective),
+}
+
+/// One of the `%`-directives recognised in a format string, as parsed by
+/// [`parse_format`].
+enum FormatDirective {
+    /// `%p` - full path, as printed by `Printer`.
+    Path,
+    /// `%f` - basename.
+    Basename,
+    /// `%h` - leading directories (path with the basename removed).
+    Dirname,
+    /// `%s` - size in bytes.
+    Size,
+    /// `%d` - depth in the directory tree the file was found under.
+    Depth,
+    /// `%y` - type letter, e.g. `f`, `d`, `l`.
+    Type,
+    /// `%m` - permissions in octal.
+    OctalPerms,
+    /// `%M` - permissions in symbolic `ls -l` form.
+    SymbolicPerms,
+    /// `%u`/`%U` - owning user, by name (falling back to numeric) or always
+    /// numeric.
+    Owner { numeric: bool },
+    /// `%g`/`%G` - owning group, by name (falling back to numeric) or always
+    /// numeric.
+    Group { numeric: bool },
+    /// `%i` - inode number.
+    Inode,
+    /// `%n` - number of hard links.
+    HardLinks,
+    /// `%l` - target of a symbolic link (empty for non-symlinks).
+    SymlinkTarget,
+    /// `%A`/`%T`/`%C` followed by a `@` or a strftime-style letter - access,
+    /// modification or status-change time.
+    Time { kind: TimeKind, format: char },
+    /// `%%` - a literal percent sign.
+    Percent,
+    /// An unrecognised directive, e.g. `%Q`. GNU find prints the directive
+    /// literally; `parse_format` has already warned about it once, at parse
+    /// time, and flagged the format as having a warning.
+    Unknown(char),
+}
+
+#[derive(Clone, Copy)]
+enum TimeKind {
+    Access,
+    Modify,
+    Change,
+}
+
+/// The result of parsing a `-printf` format string: the segments to
+/// evaluate per file, and whether parsing hit an unrecognized directive or a
+/// trailing `%` - both are printed literally (with a warning emitted
+/// immediately, here, rather than once per matched file), and both mean the
+/// overall exit code must end up as 1, matching GNU find.
+struct ParsedFormat {
+    segments: Vec<FormatSegment>,
+    had_warning: bool,
+}
+
+/// Parses a `-printf` format string into a sequence of [`FormatSegment`]s,
+/// resolving escape sequences and `%`-directives once up front so that
+/// evaluating it per file is just a walk over the `Vec`.
+fn parse_format(format: &str) -> Result<ParsedFormat, String> {
+    let mut segments = Vec::new();
+    let mut literal = Vec::new();
+    let mut had_warning = false;
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => literal.push(b'\n'),
+                Some('t') => literal.push(b'\t'),
+                Some(d) if d.is_digit(8) => {
+                    // GNU find allows up to 3 octal digits, e.g. \0, \11, \200.
+                    let mut octal = String::new();
+                    octal.push(d);
+                    while octal.len() < 3 {
+                        match chars.peek() {
+                            Some(d2) if d2.is_digit(8) => octal.push(chars.next().unwrap()),
+                            _ => break,
+                        }
+                    }
+                    let byte = u8::from_str_radix(&octal, 8)
+                        .map_err(|_| format!("invalid octal escape '\\{octal}' in format"))?;
+                    literal.push(byte);
+                }
+                Some('\\') => literal.push(b'\\'),
+                Some('a') => literal.push(0x07),
+                Some('b') => literal.push(0x08),
+                Some('f') => literal.push(0x0C),
+                Some('r') => literal.push(b'\r'),
+                Some('v') => literal.push(0x0B),
+                Some(other) => return Err(format!("unrecognized escape '\\{other}' in format")),
+                None => return Err("trailing backslash in format".to_string()),
+            },
+            '%' => {
+                if !literal.is_empty() {
+                    segments.push(FormatSegment::Literal(std::mem::take(&mut literal)));
+                }
+                let directive = match chars.next() {
+                    Some('p') => FormatDirective::Path,
+                    Some('f') => FormatDirective::Basename,
+                    Some('h') => FormatDirective::Dirname,
+                    Some('s') => FormatDirective::Size,
+                    Some('d') => FormatDirective::Depth,
+                    Some('y') => FormatDirective::Type,
+                    Some('m') => FormatDirective::OctalPerms,
+                    Some('M') => FormatDirective::SymbolicPerms,
+                    Some('u') => FormatDirective::Owner { numeric: false },
+                    Some('U') => FormatDirective::Owner { numeric: true },
+                    Some('g') => FormatDirective::Group { numeric: false },
+                    Some('G') => FormatDirective::Group { numeric: true },
+                    Some('i') => FormatDirective::Inode,
+                    Some('n') => FormatDirective::HardLinks,
+                    Some('l') => FormatDirective::SymlinkTarget,
+                    Some('%') => FormatDirective::Percent,
+                    Some(letter @ ('A' | 'T' | 'C')) => {
+                        let kind = match letter {
+                            'A' => TimeKind::Access,
+                            'T' => TimeKind::Modify,
+                            _ => TimeKind::Change,
+                        };
+                        let format = chars.next().ok_or_else(|| {
+                            format!("missing time format letter after '%{letter}'")
+                        })?;
+                        FormatDirective::Time { kind, format }
+                    }
+                    Some(other) => {
+                        writeln!(
+                            &mut stderr(),
+                            "find: warning: unrecognized format directive `%{other}'"
+                        )
+                        .unwrap();
+                        had_warning = true;
+                        FormatDirective::Unknown(other)
+                    }
+                    None => {
+                        // A trailing, unescaped '%' is printed literally.
+                        writeln!(
+                            &mut stderr(),
+                            "find: warning: format string ends in an incomplete directive"
+                        )
+                        .unwrap();
+                        had_warning = true;
+                        segments.push(FormatSegment::Literal(vec![b'%']));
+                        break;
+                    }
+                };
+                segments.push(FormatSegment::Directive(directive));
+            }
+            other => {
+                let mut buf = [0u8; 4];
+                literal.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(FormatSegment::Literal(literal));
+    }
+    Ok(ParsedFormat {
+        segments,
+        had_warning,
+    })
+}
+
+/// Formats a unix timestamp (seconds + nanoseconds) according to a single
+/// strftime-style letter, as used after `%A`/`%T`/`%C`. Covers the letters
+/// needed for the common `%TY-%Tm-%Td` style format strings; anything else
+/// is passed through so it's at least visible in the output rather than
+/// silently dropped.
+fn format_time(secs: i64, nanos: i64, format: char) -> String {
+    if format == '@' {
+        return if nanos == 0 {
+            format!("{secs}")
+        } else {
+            format!("{secs}.{nanos:09}")
+        };
+    }
+    let (year, month, day, hour, minute, second) = civil_from_epoch(secs);
+    match format {
+        'Y' => format!("{year}"),
+        'y' => format!("{:02}", year.rem_euclid(100)),
+        'm' => format!("{month:02}"),
+        'd' => format!("{day:02}"),
+        'H' => format!("{hour:02}"),
+        'M' => format!("{minute:02}"),
+        'S' => format!("{second:02}"),
+        'T' => format!("{hour:02}:{minute:02}:{second:02}"),
+        'b' | 'h' => MONTH_NAMES[(month - 1) as usize].to_string(),
+        other => format!("%{other}"),
+    }
+}
+
+fn type_letter(file_type: std::fs::FileType) -> char {
+    if file_type.is_dir() {
+        'd'
+    } else if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_file() {
+        'f'
+    } else {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_block_device() {
+            'b'
+        } else if file_type.is_char_device() {
+            'c'
+        } else if file_type.is_fifo() {
+            'p'
+        } else if file_type.is_socket() {
+            's'
+        } else {
+            'U'
+        }
+    }
+}
+
+fn symbolic_perms(mode: u32, file_type: std::fs::FileType) -> String {
+    let kind = type_letter(file_type);
+    let kind = if kind == 'f' { '-' } else { kind };
+    let triplet = |shift: u32, setid: u32, (set_x, set_no_x): (char, char)| {
+        let r = if mode & (0o4 << shift) != 0 { 'r' } else { '-' };
+        let w = if mode & (0o2 << shift) != 0 { 'w' } else { '-' };
+        let x_set = mode & setid != 0;
+        let x_bit = mode & (0o1 << shift) != 0;
+        let x = match (x_bit, x_set) {
+            (true, true) => set_x,
+            (false, true) => set_no_x,
+            (true, false) => 'x',
+            (false, false) => '-',
+        };
+        format!("{r}{w}{x}")
+    };
+    format!(
+        "{}{}{}{}",
+        kind,
+        triplet(6, 0o4000, ('s', 'S')),
+        triplet(3, 0o2000, ('s', 'S')),
+        triplet(0, 0o1000, ('t', 'T'))
+    )
+}
+
+/// A matcher implementing GNU find's `-printf`/`-fprintf`: a free-form
+/// format string with `%`-directives, parsed once at construction time.
+pub struct FormatPrinter {
+    segments: Vec<FormatSegment>,
+    /// Set when `parse_format` hit an unrecognized directive or a trailing
+    /// `%`; GNU find exits 1 in that case, in addition to the one-time
+    /// warning already printed at parse time.
+    had_warning: bool,
+    output_file: Option<RefCell<BufWriter<ArcFileWriter>>>,
+}
+
+impl FormatPrinter {
+    /// Parses `format` and builds a printer for it. Returns an error
+    /// describing the problem if the format string can't be parsed; this is
+    /// surfaced at argument-parse time rather than once per matched file.
+    pub fn new(format: &str, output_file: Option<Arc<File>>) -> Result<Self, String> {
+        let parsed = parse_format(format)?;
+        Ok(Self {
+            segments: parsed.segments,
+            had_warning: parsed.had_warning,
+            output_file: output_file.map(|f| RefCell::new(BufWriter::new(ArcFileWriter(f)))),
+        })
+    }
+
+    fn write_entry(
+        &self,
+        file_info: &WalkEntry,
+        matcher_io: &mut MatcherIO,
+        mut out: impl Write,
+        print_error_message: bool,
+    ) {
+        if self.had_warning {
+            matcher_io.set_exit_code(1);
+        }
+        let result = (|| -> std::io::Result<()> {
+            for segment in &self.segments {
+                match segment {
+                    FormatSegment::Literal(bytes) => out.write_all(bytes)?,
+                    FormatSegment::Directive(directive) => {
+                        self.write_directive(directive, file_info, &mut out)?;
+                    }
+                }
+            }
+            Ok(())
+        })();
+        match result {
+            Ok(()) => {
+                if !print_error_message && stdout_needs_line_flush() {
+                    let _ = out.flush();
+                }
+            }
+            Err(e) => {
+                if print_error_message {
+                    writeln!(
+                        &mut stderr(),
+                        "Error writing {:?} for {}",
+                        file_info.path().to_string_lossy(),
+                        e
+                    )
+                    .unwrap();
+                    matcher_io.set_exit_code(1);
+                }
+            }
+        }
+    }
+
+    fn write_directive(
+        &self,
+        directive: &FormatDirective,
+        file_info: &WalkEntry,
+        mut out: impl Write,
+    ) -> std::io::Result<()> {
+        let path = file_info.path();
+        match directive {
+            FormatDirective::Path => write!(out, "{}", path.to_string_lossy()),
+            FormatDirective::Basename => write!(
+                out,
+                "{}",
+                path.file_name().map_or_else(
+                    || path.to_string_lossy(),
+                    |name| name.to_string_lossy()
+                )
+            ),
+            FormatDirective::Dirname => write!(
+                out,
+                "{}",
+                match path.parent() {
+                    Some(p) if !p.as_os_str().is_empty() => p.to_string_lossy().to_string(),
+                    _ => ".".to_string(),
+                }
+            ),
+            FormatDirective::Size => match file_info.metadata() {
+                Ok(metadata) => write!(out, "{}", metadata.size()),
+                Err(_) => write!(out, "0"),
+            },
+            FormatDirective::Depth => write!(out, "{}", file_info.depth()),
+            FormatDirective::Type => match file_info.metadata() {
+                Ok(metadata) => write!(out, "{}", type_letter(metadata.file_type())),
+                Err(_) => write!(out, "?"),
+            },
+            FormatDirective::OctalPerms => match file_info.metadata() {
+                Ok(metadata) => write!(out, "{:o}", metadata.mode() & 0o7777),
+                Err(_) => write!(out, "0"),
+            },
+            FormatDirective::SymbolicPerms => match file_info.metadata() {
+                Ok(metadata) => write!(
+                    out,
+                    "{}",
+                    symbolic_perms(metadata.mode(), metadata.file_type())
+                ),
+                Err(_) => write!(out, "?---------"),
+            },
+            FormatDirective::Owner { numeric } => match file_info.metadata() {
+                Ok(metadata) => {
+                    let uid = metadata.uid();
+                    if *numeric {
+                        write!(out, "{uid}")
+                    } else {
+                        match uzers::get_user_by_uid(uid) {
+                            Some(user) => write!(out, "{}", user.name().to_string_lossy()),
+                            None => write!(out, "{uid}"),
+                        }
+                    }
+                }
+                Err(_) => write!(out, "?"),
+            },
+            FormatDirective::Group { numeric } => match file_info.metadata() {
+                Ok(metadata) => {
+                    let gid = metadata.gid();
+                    if *numeric {
+                        write!(out, "{gid}")
+                    } else {
+                        match uzers::get_group_by_gid(gid) {
+                            Some(group) => write!(out, "{}", group.name().to_string_lossy()),
+                            None => write!(out, "{gid}"),
+                        }
+                    }
+                }
+                Err(_) => write!(out, "?"),
+            },
+            FormatDirective::Inode => match file_info.metadata() {
+                Ok(metadata) => write!(out, "{}", metadata.ino()),
+                Err(_) => write!(out, "0"),
+            },
+            FormatDirective::HardLinks => match file_info.metadata() {
+                Ok(metadata) => write!(out, "{}", metadata.nlink()),
+                Err(_) => write!(out, "0"),
+            },
+            FormatDirective::SymlinkTarget => {
+                match std::fs::read_link(path) {
+                    Ok(target) => write!(out, "{}", target.to_string_lossy()),
+                    Err(_) => Ok(()),
+                }
+            }
+            FormatDirective::Time { kind, format } => match file_info.metadata() {
+                Ok(metadata) => {
+                    let (secs, nanos) = match kind {
+                        TimeKind::Access => (metadata.atime(), metadata.atime_nsec()),
+                        TimeKind::Modify => (metadata.mtime(), metadata.mtime_nsec()),
+                        TimeKind::Change => (metadata.ctime(), metadata.ctime_nsec()),
+                    };
+                    write!(out, "{}", format_time(secs, nanos, *format))
+                }
+                Err(_) => write!(out, "?"),
+            },
+            FormatDirective::Percent => write!(out, "%"),
+            // The warning for this was already emitted once, at parse time.
+            FormatDirective::Unknown(c) => write!(out, "%{c}"),
+        }
+    }
+}
+
+impl Matcher for FormatPrinter {
+    fn matches(&self, file_info: &WalkEntry, matcher_io: &mut MatcherIO) -> bool {
+        if let Some(file) = &self.output_file {
+            self.write_entry(file_info, matcher_io, &mut *file.borrow_mut(), true);
+        } else {
+            self.write_entry(
+                file_info,
+                matcher_io,
+                &mut *matcher_io.deps.get_output().borrow_mut(),
+                false,
+            );
+        }
+        true
+    }
+
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
+    fn finalize(&self, matcher_io: &mut MatcherIO) {
+        if self.had_warning {
+            matcher_io.set_exit_code(1);
+        }
+        if let Some(output_file) = &self.output_file {
+            flush_output_file(output_file, matcher_io);
+        }
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Splits a unix timestamp into `(year, month, day, hour, minute, second)`,
+/// in the local timezone - matching what GNU `ls`/`find -ls`/`find -printf`
+/// show. Goes through libc's `localtime_r` rather than a pure epoch→civil
+/// calculation, since getting zone offsets (including DST) right without it
+/// means reimplementing the system timezone database.
+fn civil_from_epoch(secs: i64) -> (i64, u32, u32, u32, u32, u32) {
+    // SAFETY: `tm` is a plain-old-data struct; zero-initializing it and
+    // handing `localtime_r` a valid `time_t` and `&mut tm` is exactly its
+    // documented contract.
+    let tm = unsafe {
+        let time = secs as libc::time_t;
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&time, &mut tm);
+        tm
+    };
+    (
+        1900 + i64::from(tm.tm_year),
+        (tm.tm_mon + 1) as u32,
+        tm.tm_mday as u32,
+        tm.tm_hour as u32,
+        tm.tm_min as u32,
+        tm.tm_sec as u32,
+    )
+}
+
+/// ~6 months, in seconds - the same threshold coreutils `ls` uses to decide
+/// whether to show the time-of-day or the year next to an old/future mtime.
+const LS_RECENT_THRESHOLD_SECS: i64 = 15_778_476;
+
+/// Formats an mtime the way GNU `ls`/`find -ls` do: `Mon DD HH:MM` for
+/// files modified within the last ~6 months, or `Mon DD  YYYY` for anything
+/// older (or dated in the future), so that unusual timestamps don't get
+/// mistaken for a normal recent one.
+fn format_ls_time(secs: i64) -> String {
+    let (year, month, day, hour, minute, _second) = civil_from_epoch(secs);
+    let month_name = MONTH_NAMES[(month - 1) as usize];
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    if secs > now || now - secs > LS_RECENT_THRESHOLD_SECS {
+        format!("{month_name} {day:2}  {year:4}")
+    } else {
+        format!("{month_name} {day:2} {hour:02}:{minute:02}")
+    }
+}
+
+/// A matcher implementing GNU find's `-ls`/`-fls`: a long-listing line per
+/// matched file, in the same column layout as `ls -dils` (inode, allocated
+/// size, permissions, link count, owner, group, size, mtime, name).
+pub struct Ls {
+    output_file: Option<RefCell<BufWriter<ArcFileWriter>>>,
+}
+
+impl Ls {
+    pub fn new(output_file: Option<Arc<File>>) -> Self {
+        Self {
+            output_file: output_file.map(|f| RefCell::new(BufWriter::new(ArcFileWriter(f)))),
+        }
+    }
+
+    fn write_entry(
+        &self,
+        file_info: &WalkEntry,
+        matcher_io: &mut MatcherIO,
+        mut out: impl Write,
+        print_error_message: bool,
+    ) {
+        let result = (|| -> std::io::Result<()> {
+            let metadata = file_info.metadata()?;
+            let path = file_info.path();
+            // st_blocks is always in 512-byte units; GNU find rounds that up
+            // to 1K blocks with `(blocks * 512 + 1024 - 1) / 1024`.
+            let blocks = (metadata.blocks() * 512).div_ceil(1024);
+            let perms = symbolic_perms(metadata.mode(), metadata.file_type());
+            let owner = uzers::get_user_by_uid(metadata.uid())
+                .map(|u| u.name().to_string_lossy().into_owned())
+                .unwrap_or_else(|| metadata.uid().to_string());
+            let group = uzers::get_group_by_gid(metadata.gid())
+                .map(|g| g.name().to_string_lossy().into_owned())
+                .unwrap_or_else(|| metadata.gid().to_string());
+            let time = format_ls_time(metadata.mtime());
+
+            // Column widths match GNU find's pr_list(): "%6ju %4ju %s %3ju
+            // %-8.8s %-8.8s %8ju %s %s" - owner/group are truncated to 8
+            // characters, not just padded, when they run longer.
+            write!(
+                out,
+                "{:6} {:4} {} {:3} {:<8.8} {:<8.8} {:8} {} {}",
+                metadata.ino(),
+                blocks,
+                perms,
+                metadata.nlink(),
+                owner,
+                group,
+                metadata.size(),
+                time,
+                path.to_string_lossy(),
+            )?;
+            if metadata.file_type().is_symlink() {
+                if let Ok(target) = std::fs::read_link(path) {
+                    write!(out, " -> {}", target.to_string_lossy())?;
+                }
+            }
+            writeln!(out)
+        })();
+        match result {
+            Ok(()) => {
+                if !print_error_message && stdout_needs_line_flush() {
+                    let _ = out.flush();
+                }
+            }
+            Err(e) => {
+                if print_error_message {
+                    writeln!(
+                        &mut stderr(),
+                        "Error writing {:?} for {}",
+                        file_info.path().to_string_lossy(),
+                        e
+                    )
+                    .unwrap();
+                    matcher_io.set_exit_code(1);
+                }
+            }
+        }
+    }
+}
+
+impl Matcher for Ls {
+    fn matches(&self, file_info: &WalkEntry, matcher_io: &mut MatcherIO) -> bool {
+        if let Some(file) = &self.output_file {
+            self.write_entry(file_info, matcher_io, &mut *file.borrow_mut(), true);
+        } else {
+            self.write_entry(
+                file_info,
+                matcher_io,
+                &mut *matcher_io.deps.get_output().borrow_mut(),
+                false,
+            );
+        }
+        true
+    }
+
+    fn has_side_effects(&self) -> bool {
+        true
+    }
+
+    fn finalize(&self, matcher_io: &mut MatcherIO) {
+        if let Some(output_file) = &self.output_file {
+            flush_output_file(output_file, matcher_io);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -130,8 +795,128 @@ mod tests {
 
         let matcher = Printer::new(PrintDelimiter::Newline, Some(Arc::new(dev_full)));
         let deps = FakeDependencies::new();
+        let mut matcher_io = deps.new_matcher_io();
 
+        assert!(matcher.matches(&abbbc, &mut matcher_io));
+        // The write is buffered, so the write error only surfaces once the
+        // walk finishes and the buffer is flushed.
+        matcher.finalize(&mut matcher_io);
+        assert!(deps.get_output_as_string().is_empty());
+        assert_eq!(1, deps.get_exit_code());
+    }
+
+    #[test]
+    fn format_printer_prints_path_and_basename() {
+        let abbbc = get_dir_entry_for("./test_data/simple", "abbbc");
+
+        let matcher = FormatPrinter::new("%p:%f\n", None).unwrap();
+        let deps = FakeDependencies::new();
         assert!(matcher.matches(&abbbc, &mut deps.new_matcher_io()));
+        assert_eq!(
+            fix_up_slashes("./test_data/simple/abbbc:abbbc\n"),
+            deps.get_output_as_string()
+        );
+    }
+
+    #[test]
+    fn format_printer_expands_escapes() {
+        let abbbc = get_dir_entry_for("./test_data/simple", "abbbc");
+
+        let matcher = FormatPrinter::new("%p\\t%%\\n", None).unwrap();
+        let deps = FakeDependencies::new();
+        assert!(matcher.matches(&abbbc, &mut deps.new_matcher_io()));
+        assert_eq!(
+            fix_up_slashes("./test_data/simple/abbbc\t%\n"),
+            deps.get_output_as_string()
+        );
+    }
+
+    #[test]
+    fn format_printer_rejects_trailing_backslash() {
+        assert!(FormatPrinter::new("%p\\", None).is_err());
+    }
+
+    #[test]
+    fn format_printer_expands_octal_escape_with_nonzero_leading_digit() {
+        let abbbc = get_dir_entry_for("./test_data/simple", "abbbc");
+
+        // \101 is 'A' in octal; \40 is a space.
+        let matcher = FormatPrinter::new("\\101\\40%f", None).unwrap();
+        let deps = FakeDependencies::new();
+        assert!(matcher.matches(&abbbc, &mut deps.new_matcher_io()));
+        assert_eq!("A abbbc", deps.get_output_as_string());
+    }
+
+    #[test]
+    fn format_printer_sets_exit_code_on_unknown_directive() {
+        let abbbc = get_dir_entry_for("./test_data/simple", "abbbc");
+
+        let matcher = FormatPrinter::new("%Q", None).unwrap();
+        let deps = FakeDependencies::new();
+        let mut matcher_io = deps.new_matcher_io();
+        assert!(matcher.matches(&abbbc, &mut matcher_io));
+        assert_eq!("%Q", deps.get_output_as_string());
+        assert_eq!(1, deps.get_exit_code());
+    }
+
+    #[test]
+    fn format_printer_expands_TY_Tm_Td() {
+        let abbbc = get_dir_entry_for("./test_data/simple", "abbbc");
+
+        let matcher = FormatPrinter::new("%TY-%Tm-%Td", None).unwrap();
+        let deps = FakeDependencies::new();
+        assert!(matcher.matches(&abbbc, &mut deps.new_matcher_io()));
+        // Just confirm a YYYY-MM-DD date came out, not the literal "%Y" etc.
+        let output = deps.get_output_as_string();
+        assert_eq!(10, output.len());
+        assert!(output
+            .chars()
+            .enumerate()
+            .all(|(i, c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() }));
+    }
+
+    #[test]
+    fn ls_prints_path_at_end_of_line() {
+        let subdir = get_dir_entry_for("./test_data/simple", "subdir");
+
+        let matcher = Ls::new(None);
+        let deps = FakeDependencies::new();
+        assert!(matcher.matches(&subdir, &mut deps.new_matcher_io()));
+        let output = deps.get_output_as_string();
+        assert!(output.ends_with(&fix_up_slashes("./test_data/simple/subdir\n")));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn ls_prints_error_message() {
+        let dev_full = File::open("/dev/full").unwrap();
+        let abbbc = get_dir_entry_for("./test_data/simple", "abbbc");
+
+        let matcher = Ls::new(Some(Arc::new(dev_full)));
+        let deps = FakeDependencies::new();
+        let mut matcher_io = deps.new_matcher_io();
+
+        assert!(matcher.matches(&abbbc, &mut matcher_io));
+        matcher.finalize(&mut matcher_io);
         assert!(deps.get_output_as_string().is_empty());
+        assert_eq!(1, deps.get_exit_code());
+    }
+
+    #[test]
+    fn printer_finalize_flushes_buffered_output_file() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let tmp_path = tmp_dir.path().join("out");
+        let output_file = Arc::new(File::create(&tmp_path).unwrap());
+        let abbbc = get_dir_entry_for("./test_data/simple", "abbbc");
+
+        let matcher = Printer::new(PrintDelimiter::Newline, Some(Arc::clone(&output_file)));
+        let deps = FakeDependencies::new();
+        let mut matcher_io = deps.new_matcher_io();
+
+        assert!(matcher.matches(&abbbc, &mut matcher_io));
+        matcher.finalize(&mut matcher_io);
+
+        let contents = std::fs::read_to_string(&tmp_path).unwrap();
+        assert_eq!(fix_up_slashes("./test_data/simple/abbbc\n"), contents);
     }
 }